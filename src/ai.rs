@@ -0,0 +1,228 @@
+// pluggable AI backends for summarization
+//
+// selected via `glance.ai` in git config (openai, claude, ollama),
+// defaulting to openai for backwards compatibility.
+
+use anyhow::{anyhow, bail, Result};
+use git2::Repository;
+use serde::Deserialize;
+use serde_json::json;
+
+use openai_api_rs::v1::api::Client as OpenAiClient;
+use openai_api_rs::v1::chat_completion::{self, ChatCompletionRequest};
+use openai_api_rs::v1::common::GPT4_O;
+
+const DEFAULT_CLAUDE_MODEL: &str = "claude-3-5-sonnet-20241022";
+const DEFAULT_OLLAMA_MODEL: &str = "llama3";
+const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+pub trait Summarizer {
+    fn complete(&self, prompt: String) -> Result<String>;
+
+    // human readable name used by `--check`
+    fn name(&self) -> &'static str;
+
+    // verify credentials/reachability; openai and claude send a one-word
+    // completion request, ollama only queries its tags endpoint
+    fn check(&self) -> Result<()>;
+}
+
+pub struct OpenAiSummarizer {
+    client: OpenAiClient,
+    model: String,
+}
+
+impl OpenAiSummarizer {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            client: OpenAiClient::new(api_key),
+            model,
+        }
+    }
+}
+
+impl Summarizer for OpenAiSummarizer {
+    fn complete(&self, prompt: String) -> Result<String> {
+        let req = ChatCompletionRequest::new(
+            self.model.clone(),
+            vec![chat_completion::ChatCompletionMessage {
+                role: chat_completion::MessageRole::user,
+                content: chat_completion::Content::Text(prompt),
+                name: None,
+            }],
+        );
+        let result = self.client.chat_completion(req)?;
+        Ok(result.choices[0]
+            .message
+            .content
+            .as_ref()
+            .unwrap()
+            .to_string())
+    }
+
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn check(&self) -> Result<()> {
+        self.complete("respond with the single word: ok".to_string())
+            .map(|_| ())
+    }
+}
+
+pub struct ClaudeSummarizer {
+    api_key: String,
+    model: String,
+    client: reqwest::blocking::Client,
+}
+
+impl ClaudeSummarizer {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key,
+            model,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ClaudeContentBlock {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct ClaudeMessagesResponse {
+    content: Vec<ClaudeContentBlock>,
+}
+
+impl Summarizer for ClaudeSummarizer {
+    fn complete(&self, prompt: String) -> Result<String> {
+        let response: ClaudeMessagesResponse = self
+            .client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&json!({
+                "model": self.model,
+                "max_tokens": 1024,
+                "messages": [{"role": "user", "content": prompt}],
+            }))
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        response
+            .content
+            .into_iter()
+            .next()
+            .map(|block| block.text)
+            .ok_or_else(|| anyhow!("claude returned an empty response"))
+    }
+
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn check(&self) -> Result<()> {
+        self.complete("respond with the single word: ok".to_string())
+            .map(|_| ())
+    }
+}
+
+pub struct OllamaSummarizer {
+    base_url: String,
+    model: String,
+    client: reqwest::blocking::Client,
+}
+
+impl OllamaSummarizer {
+    pub fn new(base_url: String, model: String) -> Self {
+        Self {
+            base_url,
+            model,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OllamaGenerateResponse {
+    response: String,
+}
+
+impl Summarizer for OllamaSummarizer {
+    fn complete(&self, prompt: String) -> Result<String> {
+        let url = format!("{}/api/generate", self.base_url.trim_end_matches('/'));
+        let response: OllamaGenerateResponse = self
+            .client
+            .post(url)
+            .json(&json!({
+                "model": self.model,
+                "prompt": prompt,
+                "stream": false,
+            }))
+            .send()?
+            .error_for_status()?
+            .json()?;
+        Ok(response.response)
+    }
+
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn check(&self) -> Result<()> {
+        // a lightweight reachability check against the local daemon's tags endpoint
+        let url = format!("{}/api/tags", self.base_url.trim_end_matches('/'));
+        self.client.get(url).send()?.error_for_status()?;
+        Ok(())
+    }
+}
+
+// select and build the configured backend from `glance.ai` (default: openai),
+// with `glance.<backend>.model` overriding the model name.
+pub fn build_summarizer(repo: &Repository) -> Result<Box<dyn Summarizer>> {
+    let config = repo.config()?;
+    let backend = config
+        .get_string("glance.ai")
+        .unwrap_or_else(|_| "openai".to_string());
+
+    match backend.as_str() {
+        "openai" => {
+            let key = config.get_string("glance.openai.key").map_err(|_| {
+                anyhow!(
+                    "no OpenAI key configured\nuse `git config --add glance.openai.key [openai-key]` to set one"
+                )
+            })?;
+            let model = config
+                .get_string("glance.openai.model")
+                .unwrap_or_else(|_| GPT4_O.to_string());
+            Ok(Box::new(OpenAiSummarizer::new(key, model)))
+        }
+        "claude" | "anthropic" => {
+            let key = config.get_string("glance.claude.key").map_err(|_| {
+                anyhow!(
+                    "no Claude key configured\nuse `git config --add glance.claude.key [anthropic-key]` to set one"
+                )
+            })?;
+            let model = config
+                .get_string("glance.claude.model")
+                .unwrap_or_else(|_| DEFAULT_CLAUDE_MODEL.to_string());
+            Ok(Box::new(ClaudeSummarizer::new(key, model)))
+        }
+        "ollama" => {
+            let base_url = config
+                .get_string("glance.ollama.url")
+                .unwrap_or_else(|_| DEFAULT_OLLAMA_URL.to_string());
+            let model = config
+                .get_string("glance.ollama.model")
+                .unwrap_or_else(|_| DEFAULT_OLLAMA_MODEL.to_string());
+            Ok(Box::new(OllamaSummarizer::new(base_url, model)))
+        }
+        other => bail!(
+            "unknown glance.ai backend `{}`\nuse `git config --add glance.ai [openai,claude,ollama]` to pick one",
+            other
+        ),
+    }
+}
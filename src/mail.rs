@@ -0,0 +1,110 @@
+// email delivery for composed release notes: either straight SMTP to
+// `glance.notify.to`, or an mbox-formatted message on stdout for piping
+// into `sendmail` with `--stdout-mbox`.
+
+use anyhow::{anyhow, Result};
+use git2::Repository;
+use lettre::message::{MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+pub fn recipients(repo: &Repository) -> Result<Vec<String>> {
+    let config = repo.config()?;
+    let raw = config.get_string("glance.notify.to").map_err(|_| {
+        anyhow!(
+            "no recipients configured\nuse `git config --add glance.notify.to [address]` to add one"
+        )
+    })?;
+    Ok(raw
+        .split(',')
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect())
+}
+
+// a readable plaintext fallback for clients that don't render markdown
+fn to_plaintext(markdown: &str) -> String {
+    markdown
+        .lines()
+        .map(|line| {
+            line.trim_start_matches('#')
+                .trim_start()
+                .trim_start_matches("* ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// send the composed changelog as a multipart/alternative email (plaintext
+// fallback + the markdown rendered inside a <pre>) to every configured
+// recipient, over the SMTP server configured under `glance.notify.smtp.*`
+pub fn send(repo: &Repository, subject: &str, markdown_body: &str) -> Result<()> {
+    let config = repo.config()?;
+    let host = config.get_string("glance.notify.smtp.host").map_err(|_| {
+        anyhow!("no SMTP server configured\nuse `git config --add glance.notify.smtp.host [host]`")
+    })?;
+    let from = config.get_string("glance.notify.from").map_err(|_| {
+        anyhow!("no sender configured\nuse `git config --add glance.notify.from [address]`")
+    })?;
+
+    let mut transport = SmtpTransport::relay(&host)?;
+    if let (Ok(user), Ok(password)) = (
+        config.get_string("glance.notify.smtp.user"),
+        config.get_string("glance.notify.smtp.password"),
+    ) {
+        transport = transport.credentials(Credentials::new(user, password));
+    }
+    let transport = transport.build();
+
+    let plaintext = to_plaintext(markdown_body);
+    let html = format!("<pre>{}</pre>", html_escape(markdown_body));
+
+    for address in recipients(repo)? {
+        let email = Message::builder()
+            .from(from.parse()?)
+            .to(address.parse()?)
+            .subject(subject)
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(SinglePart::plain(plaintext.clone()))
+                    .singlepart(SinglePart::html(html.clone())),
+            )?;
+        transport.send(&email)?;
+    }
+
+    Ok(())
+}
+
+// a single mbox-formatted message, e.g. for `git glance --stdout-mbox | sendmail -t`
+pub fn to_mbox(from: &str, to: &[String], subject: &str, markdown_body: &str) -> String {
+    let plaintext = to_plaintext(markdown_body);
+
+    let mut mbox = String::new();
+    mbox.push_str(&format!(
+        "From {} {}\n",
+        from,
+        chrono::Utc::now().format("%a %b %e %H:%M:%S %Y")
+    ));
+    mbox.push_str(&format!("From: {}\n", from));
+    mbox.push_str(&format!("To: {}\n", to.join(", ")));
+    mbox.push_str(&format!("Subject: {}\n", subject));
+    mbox.push_str("Content-Type: text/plain; charset=utf-8\n\n");
+
+    for line in plaintext.lines() {
+        // mbox requires escaping any line that could be mistaken for a new
+        // message's "From " separator
+        if line.starts_with("From ") {
+            mbox.push('>');
+        }
+        mbox.push_str(line);
+        mbox.push('\n');
+    }
+
+    mbox
+}
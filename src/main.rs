@@ -15,16 +15,21 @@ use clap::Parser;
 use colored::Colorize;
 use git2::Repository;
 
-use openai_api_rs::v1::api::Client;
-use openai_api_rs::v1::chat_completion::{self, ChatCompletionRequest};
-use openai_api_rs::v1::common::GPT4_O;
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::{exit, Stdio};
 use std::{fmt::Write, io::Write as ioWrite};
 
 use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 
+mod ai;
+mod mail;
+mod remote;
+mod store;
+mod version;
+
+use remote::{CommitInfo, PrInfo, RemoteGitEngine};
+use version::PrClassification;
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
@@ -36,27 +41,30 @@ struct Cli {
 
     #[arg(long)]
     check: bool,
-}
 
-#[derive(Serialize, Deserialize, Debug)]
-struct CommitInfo {
-    oid: String,
-    headline: String,
-    body: String,
-    pr: Option<String>,
-}
+    // publish the generated changelog as a release on the configured forge
+    #[arg(long)]
+    publish: bool,
 
-#[derive(Serialize, Deserialize, Debug)]
-struct PrInfo {
-    number: String,
-    title: String,
-    body: String,
-    author: String,
-    comments: Vec<String>,
-    commits: Vec<CommitInfo>,
-    url: String,
-    updated_at: String,
-    merged_at: String,
+    #[arg(long)]
+    draft: bool,
+
+    #[arg(long)]
+    prerelease: bool,
+
+    // print the recommended next version in place of the changelog and exit;
+    // the commit listing and status lines before it are still printed
+    #[arg(long)]
+    suggest_version: bool,
+
+    // email the composed release notes to `glance.notify.to`
+    #[arg(long)]
+    notify: bool,
+
+    // print the release notes as an mbox-formatted message instead, e.g.
+    // `git glance --stdout-mbox | sendmail -t`
+    #[arg(long)]
+    stdout_mbox: bool,
 }
 
 #[derive(Debug)]
@@ -76,25 +84,48 @@ fn main() -> Result<(), anyhow::Error> {
         exit(0)
     }
 
+    let engine = remote::build_remote_engine(&repo)?;
+    let store = store::build_store(&repo)?;
+
     // make the dirs we need if they're not there
     std::fs::create_dir_all(repo.path().join("glance/commits"))?;
     std::fs::create_dir_all(repo.path().join("glance/prs"))?;
 
     // get the commit list
     println!("{}", "Here is what I'm working with:".green());
-    // first, get the tip of the branch (or the -r release sha specified)
-    let tip = match &cli.release {
+
+    // `-r` also accepts a `FROM..TO` range, e.g. `v1.2.0..v1.3.0`, which
+    // pins both ends and bypasses the "last tag" guess below
+    let range = cli.release.as_ref().and_then(|r| r.split_once(".."));
+    let (range_last, release_name) = match range {
+        Some((from, to)) => (Some(from.to_string()), Some(to.to_string())),
+        None => (None, cli.release.clone()),
+    };
+
+    // first, get the tip of the branch (or the -r release/range specified)
+    let tip = match &release_name {
         Some(release) => repo.revparse_single(release).unwrap(),
         None => repo.revparse_single("HEAD").unwrap(),
     };
     println!("Tip commit:  {}", tip.id().to_string().blue());
 
-    // then, get the last commit (-l last sha specified or last tag)
-    // TODO: actually order by tag date
-    let last = match (cli.last, repo.tag_names(None)?.iter().last()) {
-        (Some(sha), _) => repo.revparse_single(&sha).unwrap(),
-        (_, Some(Some(last_tag))) => repo.revparse_single(last_tag).unwrap(),
-        (_, _) => bail!("no tags found and no last release specified"),
+    // then, get the last commit (range/-l/last tag, in that order of
+    // precedence): tags are resolved to their target commit's time so the
+    // most recent tag strictly older than the tip wins, not the lexically
+    // last one
+    let last_name = match range_last.or_else(|| cli.last.clone()) {
+        Some(name) => Some(name),
+        None => {
+            let tip_time = repo.find_commit(tip.id())?.time().seconds();
+            tags_by_date(&repo)?
+                .into_iter()
+                .find(|(_, time)| *time < tip_time)
+                .map(|(name, _)| name)
+        }
+    };
+    let last = match &last_name {
+        Some(name) => repo.revparse_single(name).unwrap(),
+        None => bail!("no tags found and no last release specified"),
     };
     println!("Last commit: {}", last.id().to_string().blue());
 
@@ -133,26 +164,23 @@ fn main() -> Result<(), anyhow::Error> {
         .progress_chars("#>-"),
     );
 
-    // get github PR information
+    // get the forge's PR information, batched where the cache misses
 
     let mut pr_list = HashMap::new();
     let mut commit_list = HashMap::new();
 
+    let pr_infos = get_pr_infos(&repo, engine.as_ref(), &commits)?;
+
     let mut pos = 0;
     commits.clone().into_iter().for_each(|commit| {
         pos += 1;
         pb.set_position(pos);
-        match get_pr_info(&repo, commit) {
-            Ok(pr_info) => match pr_info {
-                Some(pr_info) => {
-                    pr_list.insert(pr_info.number.clone(), pr_info);
-                }
-                None => {
-                    commit_list.insert(commit.to_string(), get_commit_info(&repo, commit).unwrap());
-                }
-            },
-            Err(e) => {
-                println!("Error: {}", e);
+        match pr_infos.get(&commit) {
+            Some(Some(pr_info)) => {
+                pr_list.insert(pr_info.number.clone(), pr_info.clone());
+            }
+            Some(None) | None => {
+                commit_list.insert(commit.to_string(), get_commit_info(&repo, commit).unwrap());
             }
         }
     });
@@ -190,11 +218,40 @@ fn main() -> Result<(), anyhow::Error> {
 
     pb.finish_with_message("summarized");
 
+    // classify the version bump this release implies from the PRs' tags,
+    // conventional-commit `!` markers, and `BREAKING CHANGE` bodies
+    let classifications: Vec<PrClassification> = pr_summaries
+        .iter()
+        .filter_map(|s| s.as_ref().ok())
+        .filter_map(|s| {
+            pr_list.get(&s.number).map(|pr| PrClassification {
+                tag: &s.tag,
+                title: &pr.title,
+                body: &pr.body,
+            })
+        })
+        .collect();
+    let (suggested_version, used_fallback) =
+        version::suggest_next_version(last_name.as_deref(), &classifications);
+
+    if cli.suggest_version {
+        println!("{}", suggested_version);
+        return Ok(());
+    }
+
     println!(" ");
     println!("{}", "Changelog".green());
+    if used_fallback {
+        println!(
+            "**Suggested next version:** {} (no prior semver tag found, assuming 0.1.0)",
+            suggested_version
+        );
+    } else {
+        println!("**Suggested next version:** {}", suggested_version);
+    }
 
     // if there is a tag on the tip commit, show it
-    if let Some(release) = &cli.release {
+    let release_date_header = if let Some(release) = &release_name {
         // get the date
         let commit = repo.revparse_single(release).unwrap();
         let commit = repo.find_commit(commit.id()).unwrap();
@@ -204,9 +261,13 @@ fn main() -> Result<(), anyhow::Error> {
             // format like "June 3, 2024"
             let time = time.format("%B %e, %Y").to_string();
             println!("**{}** ({})", release, time);
+            Some(time)
         } else {
             println!("**{}**", release);
+            None
         }
+    } else {
+        None
     };
 
     // group the summaries by tag field
@@ -226,11 +287,24 @@ fn main() -> Result<(), anyhow::Error> {
         }
     }
 
+    // build the plain-markdown changelog (the release body with --publish and
+    // the email body with --notify/--stdout-mbox) alongside the colored printout
+    let mut changelog = String::new();
+    writeln!(changelog, "**Suggested next version:** {}", suggested_version)?;
+    if let Some(release) = &release_name {
+        if let Some(time) = release_date_header {
+            writeln!(changelog, "**{}** ({})", release, time)?;
+        } else {
+            writeln!(changelog, "**{}**", release)?;
+        }
+    }
+
     // print out the summaries by group
     for (tag, pr_summaries) in grouped_pr_summaries.iter() {
         // capitalize the first letter in the tag
         let tag = tag.chars().next().unwrap().to_uppercase().to_string() + &tag[1..];
         println!("\n** {} **", tag.magenta());
+        writeln!(changelog, "\n## {}", tag)?;
         for &pr_summary in pr_summaries {
             println!(
                 "* {} [#{}]({})",
@@ -238,33 +312,122 @@ fn main() -> Result<(), anyhow::Error> {
                 pr_summary.number.blue(),
                 pr_summary.url
             );
+            writeln!(
+                changelog,
+                "* {} [#{}]({})",
+                pr_summary.summary, pr_summary.number, pr_summary.url
+            )?;
         }
     }
 
     if !commit_list.is_empty() {
         println!("## {}", "Other".magenta());
+        writeln!(changelog, "\n## Other")?;
     }
     // print out the commits
     for (commit_oid, commit_info) in commit_list.iter() {
         let short_oid = &commit_oid[..6];
         println!("* {} ({})", commit_info.headline, short_oid);
+        writeln!(changelog, "* {} ({})", commit_info.headline, short_oid)?;
+    }
+
+    let release_tag = release_name.clone().unwrap_or_else(|| tip.id().to_string());
+    let tip_commit = repo.find_commit(tip.id())?;
+    let release_date =
+        chrono::DateTime::<chrono::Utc>::from_timestamp(tip_commit.time().seconds(), 0)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+    if let Some(store) = &store {
+        store.record_release(
+            &release_tag,
+            &release_date,
+            &tip.id().to_string(),
+            &last.id().to_string(),
+        )?;
+
+        for pr_summary in pr_summaries.iter().filter_map(|s| s.as_ref().ok()) {
+            if let Some(pr) = pr_list.get(&pr_summary.number) {
+                store.record_pr(
+                    &release_tag,
+                    &pr_summary.number,
+                    &pr.title,
+                    &pr_summary.tag,
+                    &pr_summary.summary,
+                    &pr_summary.url,
+                    &pr.merged_at,
+                )?;
+                for commit in &pr.commits {
+                    store.record_commit(
+                        &release_tag,
+                        &commit.oid,
+                        &commit.headline,
+                        Some(&pr_summary.number),
+                    )?;
+                }
+            }
+        }
+
+        for (commit_oid, commit_info) in commit_list.iter() {
+            store.record_commit(&release_tag, commit_oid, &commit_info.headline, None)?;
+        }
+    }
+
+    if cli.publish {
+        let tag = release_name
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("--publish requires -r/--release to name the tag"))?;
+        println!("\n{}", "Publishing release".green());
+        engine.create_release(&tag, &changelog, cli.draft, cli.prerelease)?;
+        println!("{}", format!("* published {}", tag).green());
+    }
+
+    let subject = format!("Release notes: {} ({})", release_tag, release_date);
+
+    if cli.stdout_mbox {
+        let to = mail::recipients(&repo).unwrap_or_default();
+        let from = repo
+            .config()?
+            .get_string("glance.notify.from")
+            .unwrap_or_else(|_| "git-glance@localhost".to_string());
+        print!("{}", mail::to_mbox(&from, &to, &subject, &changelog));
+    } else if cli.notify {
+        println!("\n{}", "Emailing release notes".green());
+        mail::send(&repo, &subject, &changelog)?;
+        println!("{}", "* sent".green());
     }
 
     Ok(())
 }
 
-// check `gh` works
-// check openai key
+// check the configured AI backend and forge both work
 fn check_setup(repo: &Repository) {
-    let config = repo.config().unwrap();
-    let openai_key = config.get_string("glance.openai.key");
-    match openai_key {
-        Ok(_) => {
-            println!("{}", "* OpenAI key found".green());
-        }
-        Err(_) => {
-            println!("{}", "OpenAI key not found".red());
+    match ai::build_summarizer(repo) {
+        Ok(summarizer) => match summarizer.check() {
+            Ok(()) => println!(
+                "{}",
+                format!("* {} backend reachable", summarizer.name()).green()
+            ),
+            Err(e) => println!(
+                "{}",
+                format!("{} backend not reachable: {}", summarizer.name(), e).red()
+            ),
+        },
+        Err(e) => println!("{}", e.to_string().red()),
+    }
+
+    let remote = repo
+        .config()
+        .ok()
+        .and_then(|config| config.get_string("glance.remote").ok())
+        .unwrap_or_else(|| "github".to_string());
+
+    if remote != "github" {
+        match remote::build_remote_engine(repo).and_then(|engine| engine.get_tags()) {
+            Ok(_) => println!("{}", format!("* {} remote reachable", remote).green()),
+            Err(e) => println!("{}", format!("{} remote not reachable: {}", remote, e).red()),
         }
+        return;
     }
 
     let mut cmd = std::process::Command::new("gh");
@@ -333,7 +496,7 @@ Please respond with only the json data of tag and summary",
         pr.title, pr.body, commits,
     );
 
-    let response = get_ai_response(&repo, prompt)?;
+    let response = ai::build_summarizer(repo)?.complete(prompt)?;
 
     // parse the json
     // we need to strip the ```json\n``` markdown stuff
@@ -350,34 +513,15 @@ Please respond with only the json data of tag and summary",
     })
 }
 
-fn get_ai_response(repo: &Repository, prompt: String) -> Result<String, anyhow::Error> {
-    let config = repo.config()?;
-
-    /*
-    let ai_method = match config.get_string("glance.ai") {
-        Ok(ai_method) => ai_method,
-        Err(_) => bail!("no ai method configured in git config\nuse `git config --add glance.ai [openai,claude,ollama]` to set one\nthen run git config --add glance.openai.key [openai-key]"),
-    };
-    println!("Using AI method: {}", ai_method);
-    */
-
-    let openai_key = config.get_string("glance.openai.key")?;
-    let client = Client::new(openai_key);
-    let req = ChatCompletionRequest::new(
-        GPT4_O.to_string(),
-        vec![chat_completion::ChatCompletionMessage {
-            role: chat_completion::MessageRole::user,
-            content: chat_completion::Content::Text(prompt),
-            name: None,
-        }],
-    );
-    let result = client.chat_completion(req)?;
-    return Ok(result.choices[0]
-        .message
-        .content
-        .as_ref()
-        .unwrap()
-        .to_string());
+// every tag's target commit time, most recent first
+fn tags_by_date(repo: &Repository) -> Result<Vec<(String, i64)>, anyhow::Error> {
+    let mut tags = Vec::new();
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let commit = repo.revparse_single(name)?.peel_to_commit()?;
+        tags.push((name.to_string(), commit.time().seconds()));
+    }
+    tags.sort_by_key(|b| std::cmp::Reverse(b.1));
+    Ok(tags)
 }
 
 fn get_commit_info(repo: &Repository, commit: git2::Oid) -> Result<CommitInfo, anyhow::Error> {
@@ -391,150 +535,117 @@ fn get_commit_info(repo: &Repository, commit: git2::Oid) -> Result<CommitInfo, a
     Ok(commit_info)
 }
 
-// look for cached data for this commit oid in .git/glance/commits/[oid].json
-// if it exists, return it
-// if it doesn't exist, run gh pr list --json --search [oid] --state merged
-// and cache the result
-fn get_pr_info(repo: &Repository, commit: git2::Oid) -> Result<Option<PrInfo>, anyhow::Error> {
+// look for cached data for this commit oid in .git/glance/commits/[oid].json;
+// `Some(_)` means a cache file exists (possibly caching "no PR"), `None`
+// means it hasn't been fetched from the forge yet
+fn read_cached_pr_info(
+    repo: &Repository,
+    commit: git2::Oid,
+) -> Result<Option<Option<PrInfo>>, anyhow::Error> {
     let commit_path = repo
         .path()
         .join("glance/commits")
         .join(commit.to_string() + ".json");
 
+    if !commit_path.exists() {
+        return Ok(None);
+    }
+
+    let file = std::fs::File::open(commit_path)?;
+    let reader = std::io::BufReader::new(file);
+    let commit_info: CommitInfo = serde_json::from_reader(reader)?;
+    let pr_info = match commit_info.pr {
+        Some(pr) => {
+            let pr_path = repo.path().join("glance/prs").join(pr + ".json");
+            let file = std::fs::File::open(pr_path)?;
+            let reader = std::io::BufReader::new(file);
+            let pr_info: PrInfo = serde_json::from_reader(reader)?;
+            Some(pr_info)
+        }
+        None => None,
+    };
+    Ok(Some(pr_info))
+}
+
+// cache a fetch result (PR or "no PR") for `commit`, and for every commit
+// folded into that PR so later runs don't re-fetch them either
+fn cache_pr_info(
+    repo: &Repository,
+    commit: git2::Oid,
+    pr_data: &Option<PrInfo>,
+) -> Result<(), anyhow::Error> {
     let commit_object = repo.find_commit(commit)?;
+    let commit_cache = CommitInfo {
+        oid: commit.to_string(),
+        headline: commit_object.summary().unwrap().to_string(),
+        body: commit_object.message().unwrap().to_string(),
+        pr: pr_data.as_ref().map(|pr| pr.number.clone()),
+    };
+    let commit_cache_path = repo
+        .path()
+        .join("glance/commits")
+        .join(commit.to_string() + ".json");
+    let mut file = std::fs::File::create(commit_cache_path)?;
+    file.write_all(serde_json::to_string(&commit_cache)?.as_bytes())?;
 
-    if commit_path.exists() {
-        let file = std::fs::File::open(commit_path)?;
-        let reader = std::io::BufReader::new(file);
-        let commit_info: CommitInfo = serde_json::from_reader(reader)?;
-        let pr_info = match commit_info.pr {
-            Some(pr) => {
-                let pr_path = repo.path().join("glance/prs").join(pr + ".json");
-                let file = std::fs::File::open(pr_path)?;
-                let reader = std::io::BufReader::new(file);
-                let pr_info: PrInfo = serde_json::from_reader(reader)?;
-                Some(pr_info)
-            }
-            None => None,
+    let Some(pr_data) = pr_data else {
+        return Ok(());
+    };
+
+    let pr_path = repo
+        .path()
+        .join("glance/prs")
+        .join(pr_data.number.clone() + ".json");
+    let mut file = std::fs::File::create(pr_path)?;
+    file.write_all(serde_json::to_string(&pr_data)?.as_bytes())?;
+
+    for pr_commit in &pr_data.commits {
+        let commit_cache = CommitInfo {
+            oid: pr_commit.oid.clone(),
+            headline: pr_commit.headline.clone(),
+            body: pr_commit.body.clone(),
+            pr: Some(pr_data.number.clone()),
         };
-        return Ok(pr_info);
-    } else {
-        let gh_program = "gh";
-        let mut cmd = std::process::Command::new(gh_program);
-        cmd.args([
-            "pr",
-            "list",
-            "--json",
-            "number,title,author,body,comments,commits,url,updatedAt,mergedAt",
-            "--search",
-            &commit.to_string(),
-            "--state",
-            "merged",
-        ]);
-
-        cmd.stderr(Stdio::piped());
-        cmd.stdout(Stdio::piped());
-        cmd.stdin(Stdio::null());
-
-        let child = cmd.spawn().unwrap();
-        let output = child.wait_with_output().unwrap();
-
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let pr_info: serde_json::Value = serde_json::from_str(stdout.as_ref())?;
-            if pr_info[0] == serde_json::Value::Null {
-                return Ok(None);
-            }
+        let commit_cache_path = repo
+            .path()
+            .join("glance/commits")
+            .join(pr_commit.oid.clone() + ".json");
+        let mut file = std::fs::File::create(commit_cache_path)?;
+        file.write_all(serde_json::to_string(&commit_cache)?.as_bytes())?;
+    }
 
-            let commits = pr_info[0]["commits"]
-                .as_array()
-                .unwrap()
-                .iter()
-                .map(|commit| CommitInfo {
-                    oid: commit["oid"].as_str().unwrap().to_string(),
-                    headline: commit["messageHeadline"].as_str().unwrap().to_string(),
-                    body: commit["messageBody"].as_str().unwrap().to_string(),
-                    pr: Some(pr_info[0]["number"].to_string()),
-                })
-                .collect();
-
-            let pr_data = PrInfo {
-                number: pr_info[0]["number"].to_string(),
-                title: pr_info[0]["title"].as_str().unwrap().to_string(),
-                body: pr_info[0]["body"].as_str().unwrap().to_string(),
-                author: pr_info[0]["author"]["login"].as_str().unwrap().to_string(),
-                updated_at: pr_info[0]["updatedAt"].as_str().unwrap().to_string(),
-                merged_at: pr_info[0]["mergedAt"].as_str().unwrap().to_string(),
-                commits,
-                comments: vec![],
-                url: pr_info[0]["url"].as_str().unwrap().to_string(),
-            };
-
-            let pr_path = repo
-                .path()
-                .join("glance/prs")
-                .join(pr_info[0]["number"].to_string() + ".json");
-            let mut file = std::fs::File::create(pr_path)?;
-            file.write_all(serde_json::to_string(&pr_data)?.as_bytes())?;
-
-            let commit_cache = CommitInfo {
-                oid: commit.to_string(),
-                headline: commit_object.summary().unwrap().to_string(),
-                body: commit_object.message().unwrap().to_string(),
-                pr: Some(pr_info[0]["number"].to_string()),
-            };
-            let commit_cache_path = repo
-                .path()
-                .join("glance/commits")
-                .join(commit.to_string() + ".json");
-            let mut file = std::fs::File::create(commit_cache_path).unwrap();
-            file.write_all(serde_json::to_string(&commit_cache).unwrap().as_bytes())
-                .unwrap();
-
-            let commits = pr_info[0]["commits"].as_array();
-            match commits {
-                Some(commits) => {
-                    commits.iter().for_each(|commit| {
-                        let commit_cache = CommitInfo {
-                            oid: commit["oid"].as_str().unwrap().to_string(),
-                            headline: commit["messageHeadline"].as_str().unwrap().to_string(),
-                            body: commit["messageBody"].as_str().unwrap().to_string(),
-                            pr: Some(pr_info[0]["number"].to_string()),
-                        };
-                        let commit_cache_path = repo
-                            .path()
-                            .join("glance/commits")
-                            .join(commit["oid"].as_str().unwrap().to_string() + ".json");
-                        let mut file = std::fs::File::create(commit_cache_path).unwrap();
-                        file.write_all(serde_json::to_string(&commit_cache).unwrap().as_bytes())
-                            .unwrap();
-                    });
-                    return Ok(Some(pr_data));
-                }
-                None => {
-                    // nothing
-                    let commit_cache = CommitInfo {
-                        oid: commit.to_string(),
-                        headline: commit_object.summary().unwrap().to_string(),
-                        body: commit_object.message().unwrap().to_string(),
-                        pr: None,
-                    };
-                    let commit_cache_path = repo
-                        .path()
-                        .join("glance/commits")
-                        .join(commit.to_string() + ".json");
-                    let mut file = std::fs::File::create(commit_cache_path).unwrap();
-                    file.write_all(serde_json::to_string(&commit_cache).unwrap().as_bytes())
-                        .unwrap();
-                }
+    Ok(())
+}
+
+// resolve PR info for every commit in `commits`, reading what's already
+// cached on disk and handing the rest to the engine's `prs_for_commits` in
+// one call, which batches the requests where the forge supports it
+fn get_pr_infos(
+    repo: &Repository,
+    engine: &dyn RemoteGitEngine,
+    commits: &[git2::Oid],
+) -> Result<HashMap<git2::Oid, Option<PrInfo>>, anyhow::Error> {
+    let mut results = HashMap::new();
+    let mut uncached = Vec::new();
+
+    for &commit in commits {
+        match read_cached_pr_info(repo, commit)? {
+            Some(pr_info) => {
+                results.insert(commit, pr_info);
             }
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let std_both = format!("{} {}", stdout, stderr);
-            bail!("Failed to run gh: {}", std_both);
+            None => uncached.push(commit),
+        }
+    }
+
+    if !uncached.is_empty() {
+        let oids: Vec<String> = uncached.iter().map(|c| c.to_string()).collect();
+        let fetched = engine.prs_for_commits(&oids)?;
+        for commit in uncached {
+            let pr_data = fetched.get(&commit.to_string()).cloned().flatten();
+            cache_pr_info(repo, commit, &pr_data)?;
+            results.insert(commit, pr_data);
         }
     }
 
-    Ok(None)
+    Ok(results)
 }
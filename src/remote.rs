@@ -0,0 +1,666 @@
+// the forge abstraction: anything that can tell us which PR a commit
+// belongs to, list tags, and publish a release.
+//
+// selected via `glance.remote` in git config (github, gitlab, gitea),
+// defaulting to github for backwards compatibility. self-hosted GitLab/Gitea
+// instances read a base URL and token from `glance.<remote>.url` /
+// `glance.<remote>.token`.
+
+use anyhow::{anyhow, bail, Result};
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::thread;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommitInfo {
+    pub oid: String,
+    pub headline: String,
+    pub body: String,
+    pub pr: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PrInfo {
+    pub number: String,
+    pub title: String,
+    pub body: String,
+    pub author: String,
+    pub comments: Vec<String>,
+    pub commits: Vec<CommitInfo>,
+    pub url: String,
+    pub updated_at: String,
+    pub merged_at: String,
+}
+
+pub trait RemoteGitEngine {
+    // the merged PR/MR that introduced `oid`, if any
+    fn pr_for_commit(&self, oid: &str) -> Result<Option<PrInfo>>;
+
+    // tag names known to the forge, most useful for self-hosted instances
+    // where `git fetch --tags` may lag behind
+    fn get_tags(&self) -> Result<Vec<String>>;
+
+    // publish a release keyed to `tag` with `body` as the release notes
+    fn create_release(&self, tag: &str, body: &str, draft: bool, prerelease: bool) -> Result<()>;
+
+    // the merged PR/MR for each of `oids`, batched where the forge supports it.
+    // the default falls back to one `pr_for_commit` call per oid.
+    fn prs_for_commits(&self, oids: &[String]) -> Result<HashMap<String, Option<PrInfo>>> {
+        let mut results = HashMap::new();
+        for oid in oids {
+            results.insert(oid.clone(), self.pr_for_commit(oid)?);
+        }
+        Ok(results)
+    }
+}
+
+// PR lookups go through batched GraphQL requests when a token is configured
+// and shell out to the `gh` CLI once per commit otherwise. release creation
+// uses the REST API directly when a token is configured, and falls back to
+// `gh release create` otherwise.
+pub struct GitHubEngine {
+    token: Option<String>,
+}
+
+impl GitHubEngine {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    // "owner/repo" as reported by `gh`, needed to build REST API URLs
+    fn name_with_owner(&self) -> Result<String> {
+        let mut cmd = std::process::Command::new("gh");
+        cmd.args(["repo", "view", "--json", "nameWithOwner", "-q", ".nameWithOwner"]);
+        cmd.stderr(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stdin(Stdio::null());
+
+        let output = cmd.spawn()?.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to resolve repo via gh: {}", stderr);
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    // one GraphQL request asking for the merged PR associated with each oid
+    // in `oids` (at most GRAPHQL_BATCH_SIZE), keyed by oid
+    fn graphql_batch(
+        token: &str,
+        owner: &str,
+        name: &str,
+        oids: &[String],
+    ) -> Result<HashMap<String, Option<PrInfo>>> {
+        let fields = oids
+            .iter()
+            .enumerate()
+            .map(|(i, oid)| {
+                format!(
+                    r#"c{i}: object(oid: "{oid}") {{
+                        ... on Commit {{
+                            associatedPullRequests(first: 1, states: MERGED) {{
+                                nodes {{
+                                    number
+                                    title
+                                    body
+                                    url
+                                    updatedAt
+                                    mergedAt
+                                    author {{ login }}
+                                    commits(first: 100) {{
+                                        nodes {{ commit {{ oid messageHeadline messageBody }} }}
+                                    }}
+                                }}
+                            }}
+                        }}
+                    }}"#
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let query = format!(
+            r#"query {{ repository(owner: "{owner}", name: "{name}") {{ {fields} }} }}"#
+        );
+
+        let client = reqwest::blocking::Client::new();
+        let response: serde_json::Value = client
+            .post("https://api.github.com/graphql")
+            .header("Authorization", format!("Bearer {}", token))
+            .header("User-Agent", "git-glance")
+            .json(&serde_json::json!({ "query": query }))
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        if let Some(errors) = response["errors"].as_array() {
+            if !errors.is_empty() {
+                bail!("GitHub GraphQL error: {}", errors[0]["message"]);
+            }
+        }
+
+        let repository = &response["data"]["repository"];
+        let mut results = HashMap::new();
+        for (i, oid) in oids.iter().enumerate() {
+            let node = &repository[format!("c{i}")]["associatedPullRequests"]["nodes"][0];
+            if node.is_null() {
+                results.insert(oid.clone(), None);
+                continue;
+            }
+
+            let commits = node["commits"]["nodes"]
+                .as_array()
+                .map(|nodes| {
+                    nodes
+                        .iter()
+                        .map(|n| CommitInfo {
+                            oid: n["commit"]["oid"].as_str().unwrap_or_default().to_string(),
+                            headline: n["commit"]["messageHeadline"]
+                                .as_str()
+                                .unwrap_or_default()
+                                .to_string(),
+                            body: n["commit"]["messageBody"]
+                                .as_str()
+                                .unwrap_or_default()
+                                .to_string(),
+                            pr: Some(node["number"].to_string()),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            results.insert(
+                oid.clone(),
+                Some(PrInfo {
+                    number: node["number"].to_string(),
+                    title: node["title"].as_str().unwrap_or_default().to_string(),
+                    body: node["body"].as_str().unwrap_or_default().to_string(),
+                    author: node["author"]["login"].as_str().unwrap_or_default().to_string(),
+                    updated_at: node["updatedAt"].as_str().unwrap_or_default().to_string(),
+                    merged_at: node["mergedAt"].as_str().unwrap_or_default().to_string(),
+                    commits,
+                    comments: vec![],
+                    url: node["url"].as_str().unwrap_or_default().to_string(),
+                }),
+            );
+        }
+
+        Ok(results)
+    }
+}
+
+// how many commit oids go in a single GraphQL request
+const GRAPHQL_BATCH_SIZE: usize = 20;
+// how many batch requests may be in flight at once
+const GRAPHQL_CONCURRENCY: usize = 4;
+
+impl RemoteGitEngine for GitHubEngine {
+    fn pr_for_commit(&self, oid: &str) -> Result<Option<PrInfo>> {
+        let mut cmd = std::process::Command::new("gh");
+        cmd.args([
+            "pr",
+            "list",
+            "--json",
+            "number,title,author,body,comments,commits,url,updatedAt,mergedAt",
+            "--search",
+            oid,
+            "--state",
+            "merged",
+        ]);
+        cmd.stderr(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stdin(Stdio::null());
+
+        let child = cmd.spawn()?;
+        let output = child.wait_with_output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            bail!("Failed to run gh: {} {}", stdout, stderr);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let pr_info: serde_json::Value = serde_json::from_str(stdout.as_ref())?;
+        if pr_info[0] == serde_json::Value::Null {
+            return Ok(None);
+        }
+
+        let commits = pr_info[0]["commits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|commit| CommitInfo {
+                oid: commit["oid"].as_str().unwrap().to_string(),
+                headline: commit["messageHeadline"].as_str().unwrap().to_string(),
+                body: commit["messageBody"].as_str().unwrap().to_string(),
+                pr: Some(pr_info[0]["number"].to_string()),
+            })
+            .collect();
+
+        Ok(Some(PrInfo {
+            number: pr_info[0]["number"].to_string(),
+            title: pr_info[0]["title"].as_str().unwrap().to_string(),
+            body: pr_info[0]["body"].as_str().unwrap().to_string(),
+            author: pr_info[0]["author"]["login"].as_str().unwrap().to_string(),
+            updated_at: pr_info[0]["updatedAt"].as_str().unwrap().to_string(),
+            merged_at: pr_info[0]["mergedAt"].as_str().unwrap().to_string(),
+            commits,
+            comments: vec![],
+            url: pr_info[0]["url"].as_str().unwrap().to_string(),
+        }))
+    }
+
+    fn get_tags(&self) -> Result<Vec<String>> {
+        let mut cmd = std::process::Command::new("gh");
+        cmd.args(["api", "repos/{owner}/{repo}/tags", "--jq", ".[].name"]);
+        cmd.stderr(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stdin(Stdio::null());
+
+        let output = cmd.spawn()?.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to list tags via gh: {}", stderr);
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.lines().map(|l| l.to_string()).collect())
+    }
+
+    fn create_release(&self, tag: &str, body: &str, draft: bool, prerelease: bool) -> Result<()> {
+        if let Some(token) = &self.token {
+            let name_with_owner = self.name_with_owner()?;
+            let client = reqwest::blocking::Client::new();
+            client
+                .post(format!(
+                    "https://api.github.com/repos/{}/releases",
+                    name_with_owner
+                ))
+                .header("Authorization", format!("Bearer {}", token))
+                .header("Accept", "application/vnd.github+json")
+                .header("User-Agent", "git-glance")
+                .json(&serde_json::json!({
+                    "tag_name": tag,
+                    "name": tag,
+                    "body": body,
+                    "draft": draft,
+                    "prerelease": prerelease,
+                }))
+                .send()?
+                .error_for_status()?;
+            return Ok(());
+        }
+
+        let mut cmd = std::process::Command::new("gh");
+        cmd.args(["release", "create", tag, "--notes", body, "--title", tag]);
+        if draft {
+            cmd.arg("--draft");
+        }
+        if prerelease {
+            cmd.arg("--prerelease");
+        }
+        cmd.stderr(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stdin(Stdio::null());
+
+        let output = cmd.spawn()?.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to create release via gh: {}", stderr);
+        }
+        Ok(())
+    }
+
+    // batched over the GraphQL API when a token is configured, bounded to
+    // GRAPHQL_CONCURRENCY requests in flight; otherwise one `gh` subprocess
+    // per commit, same as pr_for_commit.
+    fn prs_for_commits(&self, oids: &[String]) -> Result<HashMap<String, Option<PrInfo>>> {
+        let Some(token) = &self.token else {
+            let mut results = HashMap::new();
+            for oid in oids {
+                results.insert(oid.clone(), self.pr_for_commit(oid)?);
+            }
+            return Ok(results);
+        };
+
+        let name_with_owner = self.name_with_owner()?;
+        let (owner, name) = name_with_owner
+            .split_once('/')
+            .ok_or_else(|| anyhow!("unexpected `gh repo view` output: {}", name_with_owner))?;
+
+        let mut results = HashMap::new();
+        for wave in oids
+            .chunks(GRAPHQL_BATCH_SIZE)
+            .collect::<Vec<_>>()
+            .chunks(GRAPHQL_CONCURRENCY)
+        {
+            let batches: Vec<Result<HashMap<String, Option<PrInfo>>>> = thread::scope(|scope| {
+                let handles: Vec<_> = wave
+                    .iter()
+                    .map(|chunk| {
+                        let chunk = chunk.to_vec();
+                        scope.spawn(move || Self::graphql_batch(token, owner, name, &chunk))
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+            for batch in batches {
+                results.extend(batch?);
+            }
+        }
+        Ok(results)
+    }
+}
+
+// talks to a self-hosted or gitlab.com GitLab instance's REST API directly
+pub struct GitLabEngine {
+    base_url: String,
+    project: String,
+    token: String,
+    client: reqwest::blocking::Client,
+}
+
+impl GitLabEngine {
+    pub fn new(base_url: String, project: String, token: String) -> Self {
+        Self {
+            base_url,
+            project,
+            token,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        let project = urlencoding_project(&self.project);
+        format!(
+            "{}/api/v4/projects/{}{}",
+            self.base_url.trim_end_matches('/'),
+            project,
+            path
+        )
+    }
+
+    fn commits_for_merge_request(&self, iid: &str) -> Result<Vec<CommitInfo>> {
+        let url = self.api_url(&format!("/merge_requests/{}/commits", iid));
+        let commits: Vec<serde_json::Value> = self
+            .client
+            .get(url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        Ok(commits
+            .iter()
+            .map(|commit| CommitInfo {
+                oid: commit["id"].as_str().unwrap_or_default().to_string(),
+                headline: commit["title"].as_str().unwrap_or_default().to_string(),
+                body: commit["message"].as_str().unwrap_or_default().to_string(),
+                pr: Some(iid.to_string()),
+            })
+            .collect())
+    }
+}
+
+impl RemoteGitEngine for GitLabEngine {
+    fn pr_for_commit(&self, oid: &str) -> Result<Option<PrInfo>> {
+        let url = self.api_url(&format!("/repository/commits/{}/merge_requests", oid));
+        let mrs: Vec<serde_json::Value> = self
+            .client
+            .get(url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        let Some(mr) = mrs.into_iter().find(|mr| mr["state"] == "merged") else {
+            return Ok(None);
+        };
+
+        let iid = mr["iid"].to_string();
+        let commits = self.commits_for_merge_request(&iid)?;
+
+        Ok(Some(PrInfo {
+            number: iid,
+            title: mr["title"].as_str().unwrap_or_default().to_string(),
+            body: mr["description"].as_str().unwrap_or_default().to_string(),
+            author: mr["author"]["username"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            updated_at: mr["updated_at"].as_str().unwrap_or_default().to_string(),
+            merged_at: mr["merged_at"].as_str().unwrap_or_default().to_string(),
+            commits,
+            comments: vec![],
+            url: mr["web_url"].as_str().unwrap_or_default().to_string(),
+        }))
+    }
+
+    fn get_tags(&self) -> Result<Vec<String>> {
+        let url = self.api_url("/repository/tags");
+        let tags: Vec<serde_json::Value> = self
+            .client
+            .get(url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        Ok(tags
+            .iter()
+            .filter_map(|t| t["name"].as_str().map(|s| s.to_string()))
+            .collect())
+    }
+
+    fn create_release(&self, tag: &str, body: &str, draft: bool, prerelease: bool) -> Result<()> {
+        // GitLab releases have no draft/prerelease flag; note the intent in the body instead
+        let mut description = String::new();
+        if draft {
+            description.push_str("_Draft release._\n\n");
+        }
+        if prerelease {
+            description.push_str("_Pre-release._\n\n");
+        }
+        description.push_str(body);
+
+        let url = self.api_url("/releases");
+        self.client
+            .post(url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({
+                "tag_name": tag,
+                "description": description,
+            }))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+// talks to a self-hosted or gitea.com instance's REST API directly
+pub struct GiteaEngine {
+    base_url: String,
+    owner: String,
+    repo: String,
+    token: String,
+    client: reqwest::blocking::Client,
+}
+
+impl GiteaEngine {
+    pub fn new(base_url: String, owner: String, repo: String, token: String) -> Self {
+        Self {
+            base_url,
+            owner,
+            repo,
+            token,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!(
+            "{}/api/v1/repos/{}/{}{}",
+            self.base_url.trim_end_matches('/'),
+            self.owner,
+            self.repo,
+            path
+        )
+    }
+
+    fn commits_for_pull(&self, index: &str) -> Result<Vec<CommitInfo>> {
+        let url = self.api_url(&format!("/pulls/{}/commits", index));
+        let commits: Vec<serde_json::Value> = self
+            .client
+            .get(url)
+            .header("Authorization", format!("token {}", self.token))
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        Ok(commits
+            .iter()
+            .map(|commit| {
+                let message = commit["commit"]["message"].as_str().unwrap_or_default();
+                let (headline, body) = split_commit_message(message);
+                CommitInfo {
+                    oid: commit["sha"].as_str().unwrap_or_default().to_string(),
+                    headline,
+                    body,
+                    pr: Some(index.to_string()),
+                }
+            })
+            .collect())
+    }
+}
+
+impl RemoteGitEngine for GiteaEngine {
+    fn pr_for_commit(&self, oid: &str) -> Result<Option<PrInfo>> {
+        let url = self.api_url(&format!("/commits/{}/pull", oid));
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("token {}", self.token))
+            .send()?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let pr: serde_json::Value = response.error_for_status()?.json()?;
+        if pr["merged"] != serde_json::Value::Bool(true) {
+            return Ok(None);
+        }
+
+        let number = pr["number"].to_string();
+        let commits = self.commits_for_pull(&number)?;
+
+        Ok(Some(PrInfo {
+            number,
+            title: pr["title"].as_str().unwrap_or_default().to_string(),
+            body: pr["body"].as_str().unwrap_or_default().to_string(),
+            author: pr["user"]["login"].as_str().unwrap_or_default().to_string(),
+            updated_at: pr["updated_at"].as_str().unwrap_or_default().to_string(),
+            merged_at: pr["merged_at"].as_str().unwrap_or_default().to_string(),
+            commits,
+            comments: vec![],
+            url: pr["html_url"].as_str().unwrap_or_default().to_string(),
+        }))
+    }
+
+    fn get_tags(&self) -> Result<Vec<String>> {
+        let url = self.api_url("/tags");
+        let tags: Vec<serde_json::Value> = self
+            .client
+            .get(url)
+            .header("Authorization", format!("token {}", self.token))
+            .send()?
+            .error_for_status()?
+            .json()?;
+        Ok(tags
+            .iter()
+            .filter_map(|t| t["name"].as_str().map(|s| s.to_string()))
+            .collect())
+    }
+
+    fn create_release(&self, tag: &str, body: &str, draft: bool, prerelease: bool) -> Result<()> {
+        let url = self.api_url("/releases");
+        self.client
+            .post(url)
+            .header("Authorization", format!("token {}", self.token))
+            .json(&serde_json::json!({
+                "tag_name": tag,
+                "name": tag,
+                "body": body,
+                "draft": draft,
+                "prerelease": prerelease,
+            }))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+fn urlencoding_project(project: &str) -> String {
+    project.replace('/', "%2F")
+}
+
+// Gitea's commit objects nest a single `message` field rather than GitHub/GitLab's
+// separate headline/body; the first line becomes the headline and the rest,
+// with leading blank lines dropped, the body
+fn split_commit_message(message: &str) -> (String, String) {
+    let mut lines = message.splitn(2, '\n');
+    let headline = lines.next().unwrap_or_default().to_string();
+    let body = lines.next().unwrap_or_default().trim_start_matches('\n').to_string();
+    (headline, body)
+}
+
+// select and build the configured forge from `glance.remote` (default: github)
+pub fn build_remote_engine(repo: &Repository) -> Result<Box<dyn RemoteGitEngine>> {
+    let config = repo.config()?;
+    let remote = config
+        .get_string("glance.remote")
+        .unwrap_or_else(|_| "github".to_string());
+
+    match remote.as_str() {
+        "github" => {
+            let token = config
+                .get_string("glance.github.token")
+                .ok()
+                .or_else(|| std::env::var("GH_TOKEN").ok());
+            Ok(Box::new(GitHubEngine::new(token)))
+        }
+        "gitlab" => {
+            let base_url = config
+                .get_string("glance.gitlab.url")
+                .unwrap_or_else(|_| "https://gitlab.com".to_string());
+            let project = config.get_string("glance.gitlab.project").map_err(|_| {
+                anyhow!(
+                    "no GitLab project configured\nuse `git config --add glance.gitlab.project [owner/repo]`"
+                )
+            })?;
+            let token = config.get_string("glance.gitlab.token").map_err(|_| {
+                anyhow!("no GitLab token configured\nuse `git config --add glance.gitlab.token [token]`")
+            })?;
+            Ok(Box::new(GitLabEngine::new(base_url, project, token)))
+        }
+        "gitea" => {
+            let base_url = config.get_string("glance.gitea.url").map_err(|_| {
+                anyhow!("no Gitea URL configured\nuse `git config --add glance.gitea.url [https://gitea.example.com]`")
+            })?;
+            let owner = config.get_string("glance.gitea.owner").map_err(|_| {
+                anyhow!("no Gitea owner configured\nuse `git config --add glance.gitea.owner [owner]`")
+            })?;
+            let repo_name = config.get_string("glance.gitea.repo").map_err(|_| {
+                anyhow!("no Gitea repo configured\nuse `git config --add glance.gitea.repo [repo]`")
+            })?;
+            let token = config.get_string("glance.gitea.token").map_err(|_| {
+                anyhow!("no Gitea token configured\nuse `git config --add glance.gitea.token [token]`")
+            })?;
+            Ok(Box::new(GiteaEngine::new(base_url, owner, repo_name, token)))
+        }
+        other => bail!(
+            "unknown glance.remote forge `{}`\nuse `git config --add glance.remote [github,gitlab,gitea]` to pick one",
+            other
+        ),
+    }
+}
@@ -0,0 +1,108 @@
+// optional SQLite persistence of generated release history, so a release
+// can be queried ("every feature shipped since v1.2.0") or regenerated
+// without re-hitting the forge.
+//
+// enabled by setting `glance.sqlite` in git config to a database path;
+// left unconfigured, git-glance behaves exactly as it did before.
+
+use anyhow::Result;
+use git2::Repository;
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &PathBuf) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS releases (
+                tag      TEXT PRIMARY KEY,
+                date     TEXT NOT NULL,
+                tip_oid  TEXT NOT NULL,
+                last_oid TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS prs (
+                number      TEXT NOT NULL,
+                release_tag TEXT NOT NULL,
+                title       TEXT NOT NULL,
+                tag         TEXT NOT NULL,
+                summary     TEXT NOT NULL,
+                url         TEXT NOT NULL,
+                merged_at   TEXT NOT NULL,
+                PRIMARY KEY (number, release_tag)
+            );
+            CREATE TABLE IF NOT EXISTS commits (
+                oid         TEXT NOT NULL,
+                release_tag TEXT NOT NULL,
+                headline    TEXT NOT NULL,
+                pr_number   TEXT,
+                PRIMARY KEY (oid, release_tag)
+            );",
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn record_release(&self, tag: &str, date: &str, tip_oid: &str, last_oid: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO releases (tag, date, tip_oid, last_oid) VALUES (?1, ?2, ?3, ?4)",
+            params![tag, date, tip_oid, last_oid],
+        )?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_pr(
+        &self,
+        release_tag: &str,
+        number: &str,
+        title: &str,
+        tag: &str,
+        summary: &str,
+        url: &str,
+        merged_at: &str,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO prs (number, release_tag, title, tag, summary, url, merged_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![number, release_tag, title, tag, summary, url, merged_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_commit(
+        &self,
+        release_tag: &str,
+        oid: &str,
+        headline: &str,
+        pr_number: Option<&str>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO commits (oid, release_tag, headline, pr_number)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![oid, release_tag, headline, pr_number],
+        )?;
+        Ok(())
+    }
+}
+
+// `glance.sqlite` names the database file to use, relative paths resolve
+// against the repo's `.git` directory; unset means no persistence
+pub fn build_store(repo: &Repository) -> Result<Option<Store>> {
+    let config = repo.config()?;
+    let configured = match config.get_string("glance.sqlite") {
+        Ok(path) => path,
+        Err(_) => return Ok(None),
+    };
+
+    let path = PathBuf::from(&configured);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        repo.path().join(path)
+    };
+
+    Ok(Some(Store::open(&path)?))
+}
@@ -0,0 +1,96 @@
+// recommend the next semantic version from the PR tags a release contains
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl Version {
+    // parse "v1.2.3" or "1.2.3"; anything else isn't a semver we can bump from
+    pub fn parse(tag: &str) -> Option<Self> {
+        let tag = tag.strip_prefix('v').unwrap_or(tag);
+        let mut parts = tag.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self { major, minor, patch })
+    }
+
+    pub fn bump(&self, bump: Bump) -> Self {
+        match bump {
+            Bump::Major => Self {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            Bump::Minor => Self {
+                major: self.major,
+                minor: self.minor + 1,
+                patch: 0,
+            },
+            Bump::Patch => Self {
+                major: self.major,
+                minor: self.minor,
+                patch: self.patch + 1,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+// the minimum needed from a PR to classify the version bump it implies
+pub struct PrClassification<'a> {
+    pub tag: &'a str,
+    pub title: &'a str,
+    pub body: &'a str,
+}
+
+fn is_breaking(pr: &PrClassification) -> bool {
+    conventional_commit_breaking(pr.title) || pr.body.to_uppercase().contains("BREAKING CHANGE")
+}
+
+// `feat!: ...` or `feat(scope)!: ...`
+fn conventional_commit_breaking(title: &str) -> bool {
+    title
+        .split_once(':')
+        .map(|(prefix, _)| prefix.trim_end().ends_with('!'))
+        .unwrap_or(false)
+}
+
+fn classify_bump(prs: &[PrClassification]) -> Bump {
+    if prs.iter().any(is_breaking) {
+        Bump::Major
+    } else if prs.iter().any(|pr| pr.tag == "feature") {
+        Bump::Minor
+    } else {
+        Bump::Patch
+    }
+}
+
+// returns the suggested "X.Y.Z" and whether the previous tag didn't parse as
+// semver and we fell back to 0.1.0
+pub fn suggest_next_version(previous_tag: Option<&str>, prs: &[PrClassification]) -> (String, bool) {
+    match previous_tag.and_then(Version::parse) {
+        Some(base) => (base.bump(classify_bump(prs)).to_string(), false),
+        None => ("0.1.0".to_string(), true),
+    }
+}